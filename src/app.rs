@@ -0,0 +1,118 @@
+use log::{error, info, warn};
+
+use winit::application::ApplicationHandler;
+use winit::event::{DeviceEvent, DeviceId, ElementState, KeyEvent, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::WindowId;
+
+use crate::window::WindowGfxState;
+
+pub struct App<'a> {
+    title: String,
+    width: u32,
+    height: u32,
+    state: Option<WindowGfxState<'a>>,
+}
+
+impl<'a> App<'a> {
+    pub fn new(title: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            title: title.into(),
+            width,
+            height,
+            state: None,
+        }
+    }
+
+    pub fn run(&mut self) {
+        let event_loop = EventLoop::builder()
+            .build()
+            .expect("Cannot build event loop");
+
+        // Continuously run the event loop
+        event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
+
+        event_loop.run_app(self).expect("Cannot run app");
+    }
+}
+
+impl<'a> ApplicationHandler for App<'a> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        info!("Resumed.");
+
+        self.state = Some(WindowGfxState::new(event_loop, &self.title, self.width, self.height));
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(state) = &mut self.state else {
+            return;
+        };
+
+        if window_id == state.window.id() && !state.gfx_state.input(&event) {
+            match event {
+                WindowEvent::CloseRequested | WindowEvent::KeyboardInput {
+                    event: KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::Escape),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                } => event_loop.exit(),
+                WindowEvent::Resized(physical_size) => {
+                    state.gfx_state.resize(physical_size);
+                },
+                WindowEvent::ScaleFactorChanged { .. } => {
+                    // do not handle scale factor for now
+                },
+                WindowEvent::RedrawRequested if window_id == state.window.id() => {
+                    info!("Loop");
+
+                    state.gfx_state.update();
+
+                    match state.gfx_state.render() {
+                        Ok(_) => {}
+                        // Reconfigure the surface if lost
+                        Err(wgpu::SurfaceError::Lost) => state.gfx_state.resize(state.gfx_state.size),
+                        // The system is out of memory, we should probably quit
+                        Err(wgpu::SurfaceError::OutOfMemory) => {
+                            error!("Out of memory. Cannot render.");
+                            event_loop.exit()
+                        },
+                        // All other errors (Outdated, Timeout) should be resolved by the next frame
+                        Err(e) => error!("{:?}", e),
+                    }
+
+                    state.window.request_redraw();
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &winit::event_loop::ActiveEventLoop,
+        _device_id: DeviceId,
+        _event: DeviceEvent,
+    ) {
+        // info!("Device {device_id:?} event: {event:?}");
+    }
+
+    fn suspended(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        info!("Suspended.");
+    }
+
+    fn exiting(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        info!("Exiting.");
+    }
+
+    fn memory_warning(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
+        warn!("Memory warning.");
+    }
+}
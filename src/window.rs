@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use futures::executor::block_on;
+use winit::dpi::LogicalSize;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowAttributes};
+
+use crate::state::GfxState;
+
+pub(crate) struct WindowGfxState<'a> {
+    pub(crate) window: Arc<Window>,
+    pub(crate) gfx_state: GfxState<'a>,
+}
+
+impl<'a> WindowGfxState<'a> {
+    pub(crate) fn new(event_loop: &ActiveEventLoop, title: &str, width: u32, height: u32) -> Self {
+        let window = Arc::new(event_loop
+            .create_window(
+                WindowAttributes::default()
+                    .with_title(title)
+                    .with_inner_size(LogicalSize::new(width, height))
+            )
+            .expect("Cannot create window"));
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            // Winit prevents sizing with CSS, so we have to set
+            // the size manually when on web.
+            use winit::dpi::PhysicalSize;
+            let _ = window.request_inner_size(PhysicalSize::new(width, height));
+
+            use winit::platform::web::WindowExtWebSys;
+            web_sys::window()
+                .and_then(|win| win.document())
+                .and_then(|doc| {
+                    let dst = doc.get_element_by_id("rustycubes")?;
+                    let canvas = web_sys::Element::from(window.canvas()?);
+                    dst.append_child(&canvas).ok()?;
+                    Some(())
+                })
+                .expect("Couldn't append canvas to document body.");
+        }
+
+        let gfx_state = block_on(GfxState::new(window.clone()));
+
+        Self {
+            window,
+            gfx_state
+        }
+    }
+}